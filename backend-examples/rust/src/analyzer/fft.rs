@@ -0,0 +1,268 @@
+//! Log-spaced, A-weighted FFT spectrum analyzer (the original `fft` mode).
+//!
+//! Analysis windows overlap: a [`ringbuf::HeapRb`] retains a sliding
+//! history of the last `fft_size` samples, and a new frame is emitted
+//! every `hop_size` samples rather than every full buffer refill. This
+//! decouples emission cadence from the audio callback's buffer size and
+//! avoids dropping whole buffers of audio between windows.
+//!
+//! The transform itself uses [`realfft`] rather than a complex-to-complex
+//! `rustfft` plan, since the input is real: this roughly halves the
+//! transform cost and lets input/output scratch buffers be reused across
+//! frames instead of allocated fresh each time.
+//!
+//! Bin count, frequency range, dB range, fps and the apodization window
+//! are all negotiable per client via [`Analyzer::configure`] rather than
+//! fixed at compile time.
+
+use std::time::{Duration, Instant};
+
+use realfft::{num_complex::Complex, RealFftPlanner, RealToComplex};
+use ringbuf::traits::{Consumer, Observer, Producer};
+use ringbuf::HeapRb;
+
+use crate::window::WindowKind;
+
+use super::{Analyzer, Config, ConfigureRequest};
+
+const SAMPLE_RATE: f32 = 48000.0;
+const DEFAULT_FFT_SIZE: usize = 1024;
+const DEFAULT_BINS: usize = 80;
+const DEFAULT_FPS: u32 = 120;
+const DEFAULT_START_FREQ: f32 = 100.0;
+const DEFAULT_END_FREQ: f32 = 18000.0;
+const DEFAULT_MIN_DB: f32 = -85.0;
+const DEFAULT_MAX_DB: f32 = -25.0;
+
+const MIN_BINS: usize = 8;
+const MAX_BINS: usize = 512;
+const MIN_FPS: u32 = 1;
+const MAX_FPS: u32 = 240;
+
+/// Supported `--fft-size` values, trading latency for frequency resolution.
+pub const SUPPORTED_FFT_SIZES: [usize; 4] = [1024, 2048, 4096, 8192];
+
+pub struct FftAnalyzer {
+    fft_size: usize,
+    ring: HeapRb<f32>,
+    hop_size: usize,
+    samples_since_frame: usize,
+    windowed: Vec<f32>,
+    window: Vec<f32>,
+    coherent_gain: f32,
+    fft: std::sync::Arc<dyn RealToComplex<f32>>,
+    spectrum: Vec<Complex<f32>>,
+    band_edges: Vec<(f32, f32)>,
+    a_weights: Vec<f32>,
+    sample_rate: f32,
+    bins: usize,
+    start_freq: f32,
+    end_freq: f32,
+    min_db: f32,
+    max_db: f32,
+    fps: u32,
+    last_fft_time: Instant,
+    fft_interval: Duration,
+}
+
+impl FftAnalyzer {
+    pub fn new() -> Self {
+        Self::with_options(WindowKind::Hann, DEFAULT_FFT_SIZE)
+    }
+
+    pub fn with_window(window_kind: WindowKind) -> Self {
+        Self::with_options(window_kind, DEFAULT_FFT_SIZE)
+    }
+
+    pub fn with_options(window_kind: WindowKind, fft_size: usize) -> Self {
+        let mut planner = RealFftPlanner::<f32>::new();
+        let fft = planner.plan_fft_forward(fft_size);
+        let windowed = fft.make_input_vec();
+        let spectrum = fft.make_output_vec();
+        let (window, coherent_gain) = window_kind.build(fft_size);
+
+        let mut analyzer = Self {
+            fft_size,
+            ring: HeapRb::new(fft_size),
+            hop_size: fft_size / 4,
+            samples_since_frame: 0,
+            windowed,
+            window,
+            coherent_gain,
+            fft,
+            spectrum,
+            band_edges: Vec::new(),
+            a_weights: Vec::new(),
+            sample_rate: SAMPLE_RATE,
+            bins: DEFAULT_BINS,
+            start_freq: DEFAULT_START_FREQ,
+            end_freq: DEFAULT_END_FREQ,
+            min_db: DEFAULT_MIN_DB,
+            max_db: DEFAULT_MAX_DB,
+            fps: DEFAULT_FPS,
+            last_fft_time: Instant::now(),
+            fft_interval: Duration::from_secs_f32(1.0 / DEFAULT_FPS as f32),
+        };
+        analyzer.rebuild_bands();
+        analyzer
+    }
+
+    /// Recompute band edges and A-weights from `bins`/`start_freq`/`end_freq`,
+    /// and the fft_interval from `fps`. Called on construction and whenever
+    /// a client reconfigures this analyzer.
+    fn rebuild_bands(&mut self) {
+        let n = (self.end_freq / self.start_freq).log2() / self.bins as f32;
+
+        let mut band_edges = Vec::with_capacity(self.bins);
+        let mut freq = self.start_freq;
+        for _ in 0..self.bins {
+            let freq_lo = freq;
+            freq *= 2.0_f32.powf(n);
+            band_edges.push((freq_lo, freq));
+        }
+
+        // Compute A-weighting
+        let c1 = 12194.217_f32.powi(2);
+        let c2 = 20.598997_f32.powi(2);
+        let c3 = 107.65265_f32.powi(2);
+        let c4 = 737.86223_f32.powi(2);
+
+        let mut a_weights = Vec::with_capacity(self.bins);
+        let mut freq = self.start_freq;
+        for _ in 0..self.bins {
+            let center_freq = freq * 2.0_f32.powf(n / 2.0);
+            let f2 = center_freq.powi(2);
+            let numerator = c1 * f2.powi(2);
+            let denominator = (f2 + c2) * ((f2 + c3) * (f2 + c4)).sqrt() * (f2 + c1);
+            let weight = if denominator > 0.0 { 1.2589 * numerator / denominator } else { 0.0 };
+            a_weights.push(weight);
+            freq *= 2.0_f32.powf(n);
+        }
+
+        self.band_edges = band_edges;
+        self.a_weights = a_weights;
+        self.fft_interval = Duration::from_secs_f32(1.0 / self.fps as f32);
+    }
+
+    fn interpolate(&self, magnitude: &[f32], freq: f32, bin_width: f32) -> f32 {
+        let bin_pos = freq / bin_width;
+        let bin_lo = bin_pos as usize;
+        let bin_hi = (bin_lo + 1).min(magnitude.len() - 1);
+        let ratio = bin_pos - bin_lo as f32;
+        let clamped_lo = bin_lo.min(magnitude.len() - 1);
+        magnitude[clamped_lo] + (magnitude[bin_hi] - magnitude[clamped_lo]) * ratio
+    }
+}
+
+impl Analyzer for FftAnalyzer {
+    fn process_data(&mut self, samples: &[f32]) -> Option<Vec<u8>> {
+        // Push all incoming samples into the ring; it keeps only the most
+        // recent fft_size, overwriting older ones as new samples arrive.
+        for &sample in samples {
+            self.ring.push_overwrite(sample);
+        }
+        self.samples_since_frame += samples.len();
+
+        // Not enough history yet, or not a hop boundary.
+        if self.ring.occupied_len() < self.fft_size || self.samples_since_frame < self.hop_size {
+            return None;
+        }
+        self.samples_since_frame -= self.hop_size;
+
+        // Cap emission rate so bursty input can't exceed the configured fps.
+        if self.last_fft_time.elapsed() < self.fft_interval {
+            return None;
+        }
+        self.last_fft_time = Instant::now();
+
+        // Copy the current window out of the ring (oldest to newest),
+        // applying the apodization window in place into the reused
+        // real-input scratch buffer.
+        for ((dst, src), w) in self.windowed.iter_mut().zip(self.ring.iter()).zip(self.window.iter()) {
+            *dst = *src * *w;
+        }
+
+        // Compute FFT directly from real input into the reused complex
+        // output buffer; the input and output buffers are not allocated per
+        // frame, though realfft's `process` allocates its own scratch space
+        // on each call.
+        self.fft.process(&mut self.windowed, &mut self.spectrum).expect("realfft size mismatch");
+
+        // Get magnitude, correcting for the window's coherent gain so
+        // levels stay comparable across different window choices.
+        let norm = self.fft_size as f32 * self.coherent_gain;
+        let magnitude: Vec<f32> = self.spectrum.iter().map(|c| c.norm() / norm).collect();
+
+        // Map to frequency bands
+        let bin_width = self.sample_rate / self.fft_size as f32;
+        let mut spectrum = vec![0.0_f32; self.bins];
+
+        for (i, (freq_lo, freq_hi)) in self.band_edges.iter().enumerate() {
+            let val_lo = self.interpolate(&magnitude, *freq_lo, bin_width);
+            let val_hi = self.interpolate(&magnitude, *freq_hi, bin_width);
+            let mut band_mag = val_lo.max(val_hi);
+
+            let bin_lo = (freq_lo / bin_width) as usize + 1;
+            let bin_hi = (freq_hi / bin_width) as usize;
+            if bin_hi >= bin_lo && bin_lo < magnitude.len() {
+                let bin_hi = bin_hi.min(magnitude.len() - 1);
+                for j in bin_lo..=bin_hi {
+                    band_mag = band_mag.max(magnitude[j]);
+                }
+            }
+
+            spectrum[i] = band_mag * self.a_weights[i];
+        }
+
+        // Convert to dB and normalize
+        let result: Vec<u8> = spectrum
+            .iter()
+            .map(|&v| {
+                let db = 20.0 * (v + 1e-10).log10();
+                let normalized = (db - self.min_db) / (self.max_db - self.min_db);
+                (normalized.clamp(0.0, 1.0) * 255.0) as u8
+            })
+            .collect();
+
+        Some(result)
+    }
+
+    fn set_samplerate(&mut self, rate: f32) {
+        self.sample_rate = rate;
+    }
+
+    fn config(&self) -> Config {
+        Config::new("fft", self.bins, self.fps)
+    }
+
+    fn configure(&mut self, req: &ConfigureRequest) {
+        if let Some(bins) = req.bins {
+            if (MIN_BINS..=MAX_BINS).contains(&bins) {
+                self.bins = bins;
+            }
+        }
+        let start = req.start_freq.unwrap_or(self.start_freq);
+        let end = req.end_freq.unwrap_or(self.end_freq);
+        if start > 0.0 && end > start {
+            self.start_freq = start;
+            self.end_freq = end;
+        }
+        let min_db = req.min_db.unwrap_or(self.min_db);
+        let max_db = req.max_db.unwrap_or(self.max_db);
+        if max_db > min_db {
+            self.min_db = min_db;
+            self.max_db = max_db;
+        }
+        if let Some(fps) = req.fps {
+            if (MIN_FPS..=MAX_FPS).contains(&fps) {
+                self.fps = fps;
+            }
+        }
+        if let Some(window) = req.window.as_deref().and_then(|w| w.parse::<WindowKind>().ok()) {
+            let (coeffs, gain) = window.build(self.fft_size);
+            self.window = coeffs;
+            self.coherent_gain = gain;
+        }
+        self.rebuild_bands();
+    }
+}
@@ -0,0 +1,111 @@
+//! Plain linear-bin spectrum analyzer (`linear` mode).
+//!
+//! Unlike [`super::FftAnalyzer`] this reports raw, evenly-spaced FFT bins
+//! with no log spacing and no A-weighting, which is closer to what a
+//! general-purpose spectrum analyzer shows.
+
+use std::time::{Duration, Instant};
+
+use rustfft::{num_complex::Complex, FftPlanner};
+
+use super::{Analyzer, Config};
+
+const SAMPLE_RATE: f32 = 48000.0;
+const FFT_SIZE: usize = 1024;
+const LINEAR_BINS: usize = 128;
+const LINEAR_FPS: u32 = 120;
+const LINEAR_END_FREQ: f32 = 20000.0;
+
+pub struct LinearAnalyzer {
+    buffer: Vec<f32>,
+    buffer_pos: usize,
+    window: Vec<f32>,
+    fft: std::sync::Arc<dyn rustfft::Fft<f32>>,
+    sample_rate: f32,
+    last_fft_time: Instant,
+    fft_interval: Duration,
+}
+
+impl LinearAnalyzer {
+    pub fn new() -> Self {
+        let mut planner = FftPlanner::new();
+        let fft = planner.plan_fft_forward(FFT_SIZE);
+
+        let window: Vec<f32> = (0..FFT_SIZE)
+            .map(|i| 0.5 * (1.0 - (2.0 * std::f32::consts::PI * i as f32 / (FFT_SIZE - 1) as f32).cos()))
+            .collect();
+
+        Self {
+            buffer: vec![0.0; FFT_SIZE],
+            buffer_pos: 0,
+            window,
+            fft,
+            sample_rate: SAMPLE_RATE,
+            last_fft_time: Instant::now(),
+            fft_interval: Duration::from_secs_f32(1.0 / LINEAR_FPS as f32),
+        }
+    }
+}
+
+impl Analyzer for LinearAnalyzer {
+    fn process_data(&mut self, samples: &[f32]) -> Option<Vec<u8>> {
+        for &sample in samples {
+            if self.buffer_pos < FFT_SIZE {
+                self.buffer[self.buffer_pos] = sample;
+                self.buffer_pos += 1;
+            }
+        }
+
+        if self.buffer_pos < FFT_SIZE {
+            return None;
+        }
+        if self.last_fft_time.elapsed() < self.fft_interval {
+            return None;
+        }
+
+        self.last_fft_time = Instant::now();
+        self.buffer_pos = 0;
+
+        let mut complex: Vec<Complex<f32>> = self.buffer
+            .iter()
+            .zip(self.window.iter())
+            .map(|(s, w)| Complex::new(s * w, 0.0))
+            .collect();
+
+        self.fft.process(&mut complex);
+
+        let magnitude: Vec<f32> = complex[..=FFT_SIZE / 2]
+            .iter()
+            .map(|c| c.norm() / FFT_SIZE as f32)
+            .collect();
+
+        // Evenly spaced bins from DC up to LINEAR_END_FREQ
+        let bin_width = self.sample_rate / FFT_SIZE as f32;
+        let end_bin = ((LINEAR_END_FREQ / bin_width) as usize).min(magnitude.len() - 1);
+        let bins_per_output = ((end_bin + 1) as f32 / LINEAR_BINS as f32).max(1.0);
+
+        let min_db = -85.0_f32;
+        let max_db = -25.0_f32;
+
+        let result: Vec<u8> = (0..LINEAR_BINS)
+            .map(|i| {
+                let lo = (i as f32 * bins_per_output) as usize;
+                let hi = (((i + 1) as f32 * bins_per_output) as usize).min(end_bin);
+                let peak = magnitude[lo..=hi.max(lo)].iter().cloned().fold(0.0_f32, f32::max);
+                let db = 20.0 * (peak + 1e-10).log10();
+                let normalized = (db - min_db) / (max_db - min_db);
+                (normalized.clamp(0.0, 1.0) * 255.0) as u8
+            })
+            .collect();
+
+        Some(result)
+    }
+
+    fn set_samplerate(&mut self, rate: f32) {
+        self.sample_rate = rate;
+    }
+
+    fn config(&self) -> Config {
+        Config::new("linear", LINEAR_BINS, LINEAR_FPS)
+    }
+}
@@ -0,0 +1,69 @@
+//! Broadband loudness meter analyzer (`loudness` mode).
+//!
+//! Reports a single smoothed loudness value per frame (one byte), derived
+//! from block RMS with VU-style attack/release ballistics so the meter
+//! doesn't jitter on a sample-by-sample basis.
+
+use std::time::{Duration, Instant};
+
+use super::{Analyzer, Config};
+
+const LOUDNESS_FPS: u32 = 30;
+const LOUDNESS_BLOCK_SIZE: usize = 1024;
+const LOUDNESS_MIN_DB: f32 = -60.0;
+const LOUDNESS_MAX_DB: f32 = 0.0;
+const ATTACK: f32 = 0.6;
+const RELEASE: f32 = 0.1;
+
+pub struct LoudnessAnalyzer {
+    buffer: Vec<f32>,
+    smoothed_db: f32,
+    last_emit_time: Instant,
+    emit_interval: Duration,
+}
+
+impl LoudnessAnalyzer {
+    pub fn new() -> Self {
+        Self {
+            buffer: Vec::with_capacity(LOUDNESS_BLOCK_SIZE),
+            smoothed_db: LOUDNESS_MIN_DB,
+            last_emit_time: Instant::now(),
+            emit_interval: Duration::from_secs_f32(1.0 / LOUDNESS_FPS as f32),
+        }
+    }
+}
+
+impl Analyzer for LoudnessAnalyzer {
+    fn process_data(&mut self, samples: &[f32]) -> Option<Vec<u8>> {
+        self.buffer.extend_from_slice(samples);
+
+        if self.buffer.len() < LOUDNESS_BLOCK_SIZE {
+            return None;
+        }
+        if self.last_emit_time.elapsed() < self.emit_interval {
+            self.buffer.clear();
+            return None;
+        }
+        self.last_emit_time = Instant::now();
+
+        let sum_sq: f32 = self.buffer.iter().map(|s| s * s).sum();
+        let rms = (sum_sq / self.buffer.len() as f32).sqrt();
+        self.buffer.clear();
+
+        let db = 20.0 * (rms + 1e-10).log10();
+        let coeff = if db > self.smoothed_db { ATTACK } else { RELEASE };
+        self.smoothed_db += (db - self.smoothed_db) * coeff;
+
+        let normalized = (self.smoothed_db - LOUDNESS_MIN_DB) / (LOUDNESS_MAX_DB - LOUDNESS_MIN_DB);
+        Some(vec![(normalized.clamp(0.0, 1.0) * 255.0) as u8])
+    }
+
+    fn set_samplerate(&mut self, _rate: f32) {
+        // Loudness is measured purely from block RMS; it doesn't depend
+        // on the sample rate.
+    }
+
+    fn config(&self) -> Config {
+        Config::new("loudness", 1, LOUDNESS_FPS)
+    }
+}
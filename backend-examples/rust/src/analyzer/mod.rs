@@ -0,0 +1,111 @@
+//! Pluggable analysis backends.
+//!
+//! Each [`Analyzer`] consumes mono `f32` audio samples pulled straight off
+//! the capture callback and turns them into frames of bytes ready to send
+//! to its client (each connection runs its own analyzer). Frame layout, cadence, and the number
+//! of output bins are all analyzer-specific and are advertised to the
+//! client in the `config` handshake message via [`Analyzer::config`].
+
+mod fft;
+mod linear;
+mod loudness;
+mod pitch;
+mod vu;
+
+pub use fft::{FftAnalyzer, SUPPORTED_FFT_SIZES};
+pub use linear::LinearAnalyzer;
+pub use loudness::LoudnessAnalyzer;
+pub use pitch::PitchAnalyzer;
+pub use vu::VuAnalyzer;
+
+use serde::{Deserialize, Serialize};
+
+use crate::window::WindowKind;
+
+/// Handshake message sent to a client after connecting (and again after a
+/// successful `configure` request), describing the frame shape it should
+/// expect to receive.
+#[derive(Serialize)]
+pub struct Config {
+    #[serde(rename = "type")]
+    pub msg_type: String,
+    pub mode: String,
+    pub bins: usize,
+    pub fps: u32,
+    /// Present only when the connection negotiated keystream obfuscation;
+    /// the client derives the same XOR keystream from this seed.
+    #[serde(skip_serializing_if = "Option::is_none", rename = "obfuscationSeed")]
+    pub obfuscation_seed: Option<u32>,
+}
+
+impl Config {
+    pub fn new(mode: &str, bins: usize, fps: u32) -> Self {
+        Self {
+            msg_type: "config".to_string(),
+            mode: mode.to_string(),
+            bins,
+            fps,
+            obfuscation_seed: None,
+        }
+    }
+
+    pub fn with_obfuscation_seed(mut self, seed: Option<u32>) -> Self {
+        self.obfuscation_seed = seed;
+        self
+    }
+}
+
+/// A client-initiated `{"type":"configure",...}` request. All fields are
+/// optional: a client only sends the ones it wants to change, and an
+/// analyzer keeps its current value for anything omitted or invalid.
+#[derive(Deserialize, Debug, Default)]
+pub struct ConfigureRequest {
+    #[serde(rename = "type")]
+    pub msg_type: Option<String>,
+    pub bins: Option<usize>,
+    #[serde(rename = "startFreq")]
+    pub start_freq: Option<f32>,
+    #[serde(rename = "endFreq")]
+    pub end_freq: Option<f32>,
+    #[serde(rename = "minDb")]
+    pub min_db: Option<f32>,
+    #[serde(rename = "maxDb")]
+    pub max_db: Option<f32>,
+    pub fps: Option<u32>,
+    /// One of "hann", "hamming", "blackman", "blackman-harris", "rectangular".
+    pub window: Option<String>,
+}
+
+/// A pluggable measurement mode.
+///
+/// Implementations own whatever buffering and rate-limiting they need and
+/// decide for themselves when a frame is ready to emit.
+pub trait Analyzer: Send {
+    /// Feed newly captured samples in; returns a frame when one is ready.
+    fn process_data(&mut self, data: &[f32]) -> Option<Vec<u8>>;
+
+    /// Update the sample rate the analyzer should assume for incoming
+    /// audio, re-deriving any rate-dependent bin math.
+    fn set_samplerate(&mut self, rate: f32);
+
+    /// Describe this analyzer for the client `config` handshake message.
+    fn config(&self) -> Config;
+
+    /// Apply a client-requested reconfiguration (bin count, frequency
+    /// range, dB range, fps, window). Only the `fft` analyzer applies
+    /// these; the `linear`, `vu`, `loudness` and `pitch` analyzers keep
+    /// their fixed settings and leave this as a no-op.
+    fn configure(&mut self, _req: &ConfigureRequest) {}
+}
+
+/// Build the analyzer requested by `--mode`, falling back to `fft`.
+/// `window` and `fft_size` only apply to the `fft` mode.
+pub fn from_mode(mode: &str, window: WindowKind, fft_size: usize) -> Box<dyn Analyzer> {
+    match mode {
+        "linear" => Box::new(LinearAnalyzer::new()),
+        "vu" => Box::new(VuAnalyzer::new()),
+        "loudness" => Box::new(LoudnessAnalyzer::new()),
+        "pitch" => Box::new(PitchAnalyzer::new()),
+        _ => Box::new(FftAnalyzer::with_options(window, fft_size)),
+    }
+}
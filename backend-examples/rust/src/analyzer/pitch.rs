@@ -0,0 +1,155 @@
+//! Monophonic pitch / note-detection analyzer (`pitch` mode), useful for
+//! building an instrument tuner on top of the WebSocket stream.
+//!
+//! Uses the Harmonic Product Spectrum: the windowed FFT magnitude is
+//! multiplied by itself downsampled by 2, 3 and 4, which reinforces the
+//! fundamental and suppresses overtones. The bin maximizing that product
+//! (restricted to the ~50-1000 Hz fundamental range) is refined to
+//! sub-bin accuracy with parabolic interpolation, then converted to a
+//! frequency, nearest MIDI note, and cents deviation.
+
+use std::time::{Duration, Instant};
+
+use realfft::{num_complex::Complex, RealFftPlanner, RealToComplex};
+use ringbuf::traits::{Consumer, Observer, Producer};
+use ringbuf::HeapRb;
+use serde::Serialize;
+
+use super::{Analyzer, Config};
+
+const SAMPLE_RATE: f32 = 48000.0;
+const FFT_SIZE: usize = 4096;
+const HOP_SIZE: usize = FFT_SIZE / 4;
+const PITCH_FPS: u32 = 30;
+const NUM_HARMONICS: usize = 4;
+const MIN_FREQ: f32 = 50.0;
+const MAX_FREQ: f32 = 1000.0;
+const SILENCE_RMS_THRESHOLD: f32 = 0.01;
+
+#[derive(Serialize)]
+struct PitchFrame {
+    freq: f32,
+    note: i32,
+    cents: f32,
+}
+
+pub struct PitchAnalyzer {
+    ring: HeapRb<f32>,
+    hop_size: usize,
+    samples_since_frame: usize,
+    windowed: Vec<f32>,
+    window: Vec<f32>,
+    fft: std::sync::Arc<dyn RealToComplex<f32>>,
+    spectrum: Vec<Complex<f32>>,
+    sample_rate: f32,
+    last_frame_time: Instant,
+    frame_interval: Duration,
+}
+
+impl PitchAnalyzer {
+    pub fn new() -> Self {
+        let mut planner = RealFftPlanner::<f32>::new();
+        let fft = planner.plan_fft_forward(FFT_SIZE);
+        let windowed = fft.make_input_vec();
+        let spectrum = fft.make_output_vec();
+
+        let window: Vec<f32> = (0..FFT_SIZE)
+            .map(|i| 0.5 * (1.0 - (2.0 * std::f32::consts::PI * i as f32 / (FFT_SIZE - 1) as f32).cos()))
+            .collect();
+
+        Self {
+            ring: HeapRb::new(FFT_SIZE),
+            hop_size: HOP_SIZE,
+            samples_since_frame: 0,
+            windowed,
+            window,
+            fft,
+            spectrum,
+            sample_rate: SAMPLE_RATE,
+            last_frame_time: Instant::now(),
+            frame_interval: Duration::from_secs_f32(1.0 / PITCH_FPS as f32),
+        }
+    }
+}
+
+impl Analyzer for PitchAnalyzer {
+    fn process_data(&mut self, samples: &[f32]) -> Option<Vec<u8>> {
+        for &sample in samples {
+            self.ring.push_overwrite(sample);
+        }
+        self.samples_since_frame += samples.len();
+
+        if self.ring.occupied_len() < FFT_SIZE || self.samples_since_frame < self.hop_size {
+            return None;
+        }
+        self.samples_since_frame -= self.hop_size;
+
+        if self.last_frame_time.elapsed() < self.frame_interval {
+            return None;
+        }
+        self.last_frame_time = Instant::now();
+
+        // Silence guard: skip reporting a pitch if there's nothing to track.
+        let sum_sq: f32 = self.ring.iter().map(|s| s * s).sum();
+        let rms = (sum_sq / FFT_SIZE as f32).sqrt();
+        if rms < SILENCE_RMS_THRESHOLD {
+            return None;
+        }
+
+        for ((dst, src), w) in self.windowed.iter_mut().zip(self.ring.iter()).zip(self.window.iter()) {
+            *dst = *src * *w;
+        }
+        self.fft.process(&mut self.windowed, &mut self.spectrum).expect("realfft size mismatch");
+
+        let magnitude: Vec<f32> = self.spectrum.iter().map(|c| c.norm()).collect();
+        let bin_width = self.sample_rate / FFT_SIZE as f32;
+
+        // Harmonic product spectrum over the fundamental search range.
+        let bin_lo = ((MIN_FREQ / bin_width) as usize).max(1);
+        let bin_hi = ((MAX_FREQ / bin_width) as usize).min((magnitude.len() - 1) / NUM_HARMONICS);
+        if bin_hi <= bin_lo + 1 {
+            return None;
+        }
+
+        let mut hps = vec![0.0_f32; bin_hi + 1];
+        for k in bin_lo..=bin_hi {
+            let mut product = magnitude[k];
+            for harmonic in 2..=NUM_HARMONICS {
+                product *= magnitude[k * harmonic];
+            }
+            hps[k] = product;
+        }
+
+        let peak_bin = (bin_lo..=bin_hi).max_by(|&a, &b| hps[a].total_cmp(&hps[b]))?;
+        if hps[peak_bin] <= 0.0 {
+            return None;
+        }
+
+        // Parabolic interpolation over log-magnitudes around the peak.
+        let log_mag = |k: usize| (hps[k] + 1e-10).ln();
+        let delta = if peak_bin > bin_lo && peak_bin < bin_hi {
+            let alpha = log_mag(peak_bin - 1);
+            let beta = log_mag(peak_bin);
+            let gamma = log_mag(peak_bin + 1);
+            let denom = alpha - 2.0 * beta + gamma;
+            if denom.abs() > 1e-6 { 0.5 * (alpha - gamma) / denom } else { 0.0 }
+        } else {
+            0.0
+        };
+
+        let freq = (peak_bin as f32 + delta) * bin_width;
+        let note_f = 69.0 + 12.0 * (freq / 440.0).log2();
+        let note = note_f.round() as i32;
+        let cents = (note_f - note as f32) * 100.0;
+
+        Some(serde_json::to_vec(&PitchFrame { freq, note, cents }).unwrap())
+    }
+
+    fn set_samplerate(&mut self, rate: f32) {
+        self.sample_rate = rate;
+    }
+
+    fn config(&self) -> Config {
+        Config::new("pitch", 1, PITCH_FPS)
+    }
+}
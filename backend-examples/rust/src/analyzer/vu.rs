@@ -0,0 +1,68 @@
+//! RMS/peak VU meter analyzer (`vu` mode).
+//!
+//! Works directly on time-domain samples, no FFT involved. Each frame is
+//! a pair of bytes: RMS level and peak level, both normalized 0-255.
+
+use std::time::{Duration, Instant};
+
+use super::{Analyzer, Config};
+
+const VU_FPS: u32 = 60;
+const VU_BLOCK_SIZE: usize = 512;
+const VU_MIN_DB: f32 = -60.0;
+const VU_MAX_DB: f32 = 0.0;
+
+pub struct VuAnalyzer {
+    buffer: Vec<f32>,
+    last_emit_time: Instant,
+    emit_interval: Duration,
+}
+
+impl VuAnalyzer {
+    pub fn new() -> Self {
+        Self {
+            buffer: Vec::with_capacity(VU_BLOCK_SIZE),
+            last_emit_time: Instant::now(),
+            emit_interval: Duration::from_secs_f32(1.0 / VU_FPS as f32),
+        }
+    }
+
+    fn db_to_byte(db: f32) -> u8 {
+        let normalized = (db - VU_MIN_DB) / (VU_MAX_DB - VU_MIN_DB);
+        (normalized.clamp(0.0, 1.0) * 255.0) as u8
+    }
+}
+
+impl Analyzer for VuAnalyzer {
+    fn process_data(&mut self, samples: &[f32]) -> Option<Vec<u8>> {
+        self.buffer.extend_from_slice(samples);
+
+        if self.buffer.len() < VU_BLOCK_SIZE {
+            return None;
+        }
+        if self.last_emit_time.elapsed() < self.emit_interval {
+            self.buffer.clear();
+            return None;
+        }
+        self.last_emit_time = Instant::now();
+
+        let sum_sq: f32 = self.buffer.iter().map(|s| s * s).sum();
+        let rms = (sum_sq / self.buffer.len() as f32).sqrt();
+        let peak = self.buffer.iter().fold(0.0_f32, |m, s| m.max(s.abs()));
+        self.buffer.clear();
+
+        let rms_db = 20.0 * (rms + 1e-10).log10();
+        let peak_db = 20.0 * (peak + 1e-10).log10();
+
+        Some(vec![Self::db_to_byte(rms_db), Self::db_to_byte(peak_db)])
+    }
+
+    fn set_samplerate(&mut self, _rate: f32) {
+        // VU metering is sample-rate independent: it only looks at
+        // amplitude over a fixed-size block of samples.
+    }
+
+    fn config(&self) -> Config {
+        Config::new("vu", 2, VU_FPS)
+    }
+}
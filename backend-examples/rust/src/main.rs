@@ -1,35 +1,46 @@
 //! FFT WebSocket Server - Rust Example
 //!
-//! Captures audio from system input, computes FFT, and streams frequency
-//! data to WebSocket clients for visualization.
+//! Captures audio from system input and streams it to per-client
+//! [`analyzer`] instances, each producing frames tailored to that client's
+//! WebSocket connection.
 //!
 //! Usage:
-//!     cargo run -- [--port 3001]
+//!     cargo run -- [--port 3001] [--mode fft|linear|vu|loudness|pitch] [--window hann|hamming|blackman|blackman-harris|rectangular] [--fft-size 1024|2048|4096|8192] [--device <name>] [--list-devices] [--transport ws|tcp] [--obfuscate]
 //!
 //! Protocol:
-//!     1. Client connects to ws://host:port/
+//!     1. Client connects to ws://host:port/ (or a raw TCP socket with --transport tcp)
 //!     2. Server sends config: {"type":"config","mode":"fft","bins":80,"fps":120}
-//!     3. Server streams binary: 80 bytes of uint8 (frequency magnitudes 0-255)
-
-use std::sync::{Arc, Mutex};
-use std::time::{Duration, Instant};
+//!        (plus "obfuscationSeed" when --obfuscate is set)
+//!     3. Client may request different settings:
+//!        {"type":"configure","bins":64,"startFreq":50,"endFreq":20000,"minDb":-90,"maxDb":-20,"fps":60}
+//!        the server validates them, rebuilds that client's analyzer, and
+//!        replies with an updated `config` message. Only the `fft` mode
+//!        applies these settings; other modes reply with their unchanged
+//!        config. Raw TCP clients are output-only and skip this step.
+//!     4. Server streams binary frames whose length matches `bins`, except in
+//!        `pitch` mode, where each frame is a JSON object
+//!        {"freq":..,"note":..,"cents":..} of variable length. Over raw
+//!        TCP each frame is prefixed with a big-endian u32 byte count.
+
+mod analyzer;
+mod transport;
+mod window;
+
+use std::time::Duration;
 
 use clap::Parser;
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
-use futures_util::{SinkExt, StreamExt};
-use rustfft::{num_complex::Complex, FftPlanner};
-use serde::Serialize;
+use futures_util::StreamExt;
 use tokio::net::TcpListener;
 use tokio::sync::broadcast;
 use tokio_tungstenite::tungstenite::Message;
 
+use analyzer::{Analyzer, ConfigureRequest};
+use transport::{Keystream, TransportKind, Writer};
+use window::WindowKind;
+
 // Configuration
 const SAMPLE_RATE: u32 = 48000;
-const FFT_SIZE: usize = 1024;
-const FFT_BINS: usize = 80;
-const FFT_FPS: u32 = 120;
-const FFT_START_FREQ: f32 = 100.0;
-const FFT_END_FREQ: f32 = 18000.0;
 
 #[derive(Parser, Debug)]
 #[command(name = "fft-server")]
@@ -37,189 +48,132 @@ const FFT_END_FREQ: f32 = 18000.0;
 struct Args {
     #[arg(short, long, default_value_t = 3001)]
     port: u16,
-}
 
-#[derive(Serialize)]
-struct Config {
-    #[serde(rename = "type")]
-    msg_type: String,
+    /// Analyzer mode: fft, linear, vu, loudness, pitch
+    #[arg(short, long, default_value = "fft")]
     mode: String,
-    bins: usize,
-    fps: u32,
-}
 
-struct FFTProcessor {
-    buffer: Vec<f32>,
-    buffer_pos: usize,
-    window: Vec<f32>,
-    fft: Arc<dyn rustfft::Fft<f32>>,
-    band_edges: Vec<(f32, f32)>,
-    a_weights: Vec<f32>,
-    last_fft_time: Instant,
-    fft_interval: Duration,
-}
+    /// Apodization window for the fft mode: hann, hamming, blackman, blackman-harris, rectangular
+    #[arg(short, long, default_value = "hann")]
+    window: String,
 
-impl FFTProcessor {
-    fn new() -> Self {
-        let mut planner = FftPlanner::new();
-        let fft = planner.plan_fft_forward(FFT_SIZE);
-
-        // Create Hann window
-        let window: Vec<f32> = (0..FFT_SIZE)
-            .map(|i| 0.5 * (1.0 - (2.0 * std::f32::consts::PI * i as f32 / (FFT_SIZE - 1) as f32).cos()))
-            .collect();
-
-        // Compute band edges
-        let n = (FFT_END_FREQ / FFT_START_FREQ).log2() / FFT_BINS as f32;
-        let mut band_edges = Vec::with_capacity(FFT_BINS);
-        let mut freq = FFT_START_FREQ;
-        for _ in 0..FFT_BINS {
-            let freq_lo = freq;
-            freq *= 2.0_f32.powf(n);
-            band_edges.push((freq_lo, freq));
-        }
+    /// FFT size for the fft mode, trading latency for frequency resolution: 1024, 2048, 4096, 8192
+    #[arg(long, default_value_t = 1024)]
+    fft_size: usize,
 
-        // Compute A-weighting
-        let c1 = 12194.217_f32.powi(2);
-        let c2 = 20.598997_f32.powi(2);
-        let c3 = 107.65265_f32.powi(2);
-        let c4 = 737.86223_f32.powi(2);
-
-        let mut a_weights = Vec::with_capacity(FFT_BINS);
-        let mut freq = FFT_START_FREQ;
-        for _ in 0..FFT_BINS {
-            let center_freq = freq * 2.0_f32.powf(n / 2.0);
-            let f2 = center_freq.powi(2);
-            let numerator = c1 * f2.powi(2);
-            let denominator = (f2 + c2) * ((f2 + c3) * (f2 + c4)).sqrt() * (f2 + c1);
-            let weight = if denominator > 0.0 { 1.2589 * numerator / denominator } else { 0.0 };
-            a_weights.push(weight);
-            freq *= 2.0_f32.powf(n);
-        }
+    /// Input device name (case-insensitive substring match); falls back to the default device
+    #[arg(long)]
+    device: Option<String>,
 
-        Self {
-            buffer: vec![0.0; FFT_SIZE],
-            buffer_pos: 0,
-            window,
-            fft,
-            band_edges,
-            a_weights,
-            last_fft_time: Instant::now(),
-            fft_interval: Duration::from_secs_f32(1.0 / FFT_FPS as f32),
-        }
-    }
+    /// List available input devices and exit
+    #[arg(long, default_value_t = false)]
+    list_devices: bool,
 
-    fn process(&mut self, samples: &[f32]) -> Option<Vec<u8>> {
-        // Add samples to buffer
-        for &sample in samples {
-            if self.buffer_pos < FFT_SIZE {
-                self.buffer[self.buffer_pos] = sample;
-                self.buffer_pos += 1;
-            }
-        }
+    /// Output transport for new connections: ws (WebSocket, browser-facing)
+    /// or tcp (raw, length-prefixed, for lightweight native consumers)
+    #[arg(long, default_value = "ws")]
+    transport: String,
 
-        // Check if we have enough samples and rate limiting
-        if self.buffer_pos < FFT_SIZE {
-            return None;
-        }
-        if self.last_fft_time.elapsed() < self.fft_interval {
-            return None;
-        }
+    /// Obfuscate frames with a per-run seeded XOR keystream, negotiated
+    /// with the client via the config handshake. Not encryption - just
+    /// keeps casual packet inspection from making sense of the stream.
+    #[arg(long, default_value_t = false)]
+    obfuscate: bool,
+}
+
+/// Derive a per-run keystream seed from the current time, falling back to
+/// a fixed constant if the clock is somehow before the epoch.
+fn random_seed() -> u32 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos() ^ (d.as_secs() as u32))
+        .unwrap_or(0x2545F491)
+}
 
-        self.last_fft_time = Instant::now();
-        self.buffer_pos = 0;
-
-        // Apply window
-        let mut complex: Vec<Complex<f32>> = self.buffer
-            .iter()
-            .zip(self.window.iter())
-            .map(|(s, w)| Complex::new(s * w, 0.0))
-            .collect();
-
-        // Compute FFT
-        self.fft.process(&mut complex);
-
-        // Get magnitude
-        let magnitude: Vec<f32> = complex[..=FFT_SIZE / 2]
-            .iter()
-            .map(|c| c.norm() / FFT_SIZE as f32)
-            .collect();
-
-        // Map to frequency bands
-        let bin_width = SAMPLE_RATE as f32 / FFT_SIZE as f32;
-        let mut spectrum = vec![0.0_f32; FFT_BINS];
-
-        for (i, (freq_lo, freq_hi)) in self.band_edges.iter().enumerate() {
-            let val_lo = self.interpolate(&magnitude, *freq_lo, bin_width);
-            let val_hi = self.interpolate(&magnitude, *freq_hi, bin_width);
-            let mut band_mag = val_lo.max(val_hi);
-
-            let bin_lo = (freq_lo / bin_width) as usize + 1;
-            let bin_hi = (freq_hi / bin_width) as usize;
-            if bin_hi >= bin_lo && bin_lo < magnitude.len() {
-                let bin_hi = bin_hi.min(magnitude.len() - 1);
-                for j in bin_lo..=bin_hi {
-                    band_mag = band_mag.max(magnitude[j]);
+/// Pick the input device matching `wanted` (case-insensitive substring),
+/// falling back to the host's default with a warning if nothing matches.
+fn select_device(host: &cpal::Host, wanted: Option<&str>) -> cpal::Device {
+    if let Some(wanted) = wanted {
+        let needle = wanted.to_lowercase();
+        if let Ok(devices) = host.input_devices() {
+            for device in devices {
+                if let Ok(name) = device.name() {
+                    if name.to_lowercase().contains(&needle) {
+                        return device;
+                    }
                 }
             }
-
-            spectrum[i] = band_mag * self.a_weights[i];
         }
+        eprintln!("No input device matching \"{}\", falling back to default", wanted);
+    }
 
-        // Convert to dB and normalize
-        let min_db = -85.0_f32;
-        let max_db = -25.0_f32;
-
-        let result: Vec<u8> = spectrum
-            .iter()
-            .map(|&v| {
-                let db = 20.0 * (v + 1e-10).log10();
-                let normalized = (db - min_db) / (max_db - min_db);
-                (normalized.clamp(0.0, 1.0) * 255.0) as u8
-            })
-            .collect();
+    host.default_input_device().expect("No input device available")
+}
 
-        Some(result)
+/// Validate that `desired` is within a supported range for mono capture on
+/// `device`, falling back to the nearest supported rate otherwise. If the
+/// device offers no mono config, the ranges of all its configs are used.
+fn negotiate_sample_rate(device: &cpal::Device, desired: u32) -> u32 {
+    let configs: Vec<cpal::SupportedStreamConfigRange> = match device.supported_input_configs() {
+        Ok(configs) => configs.collect(),
+        Err(_) => return desired,
+    };
+    if configs.is_empty() {
+        return desired;
     }
 
-    fn interpolate(&self, magnitude: &[f32], freq: f32, bin_width: f32) -> f32 {
-        let bin_pos = freq / bin_width;
-        let bin_lo = bin_pos as usize;
-        let bin_hi = (bin_lo + 1).min(magnitude.len() - 1);
-        let ratio = bin_pos - bin_lo as f32;
-        let clamped_lo = bin_lo.min(magnitude.len() - 1);
-        magnitude[clamped_lo] + (magnitude[bin_hi] - magnitude[clamped_lo]) * ratio
+    let mono: Vec<&cpal::SupportedStreamConfigRange> = configs.iter().filter(|c| c.channels() == 1).collect();
+    let candidates: Vec<&cpal::SupportedStreamConfigRange> = if mono.is_empty() { configs.iter().collect() } else { mono };
+
+    if candidates.iter().any(|c| c.min_sample_rate().0 <= desired && desired <= c.max_sample_rate().0) {
+        return desired;
     }
+
+    eprintln!("Device doesn't support {} Hz, picking the nearest supported rate", desired);
+    candidates
+        .iter()
+        .flat_map(|c| [c.min_sample_rate().0, c.max_sample_rate().0])
+        .min_by_key(|&rate| (rate as i64 - desired as i64).abs())
+        .unwrap_or(desired)
 }
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args = Args::parse();
+    let window: WindowKind = args.window.parse().expect("invalid --window");
+    if !analyzer::SUPPORTED_FFT_SIZES.contains(&args.fft_size) {
+        panic!("invalid --fft-size {}: must be one of {:?}", args.fft_size, analyzer::SUPPORTED_FFT_SIZES);
+    }
+    let fft_size = args.fft_size;
+    let transport_kind: TransportKind = args.transport.parse().expect("invalid --transport");
+    let obfuscation_seed = args.obfuscate.then(random_seed);
 
-    // Create broadcast channel for FFT data
-    let (tx, _) = broadcast::channel::<Vec<u8>>(16);
-    let tx_clone = tx.clone();
-
-    // Start audio capture in a separate thread
-    let processor = Arc::new(Mutex::new(FFTProcessor::new()));
-    let processor_clone = processor.clone();
-
-    std::thread::spawn(move || {
-        let host = cpal::default_host();
+    let host = cpal::default_host();
 
+    if args.list_devices {
         println!("Available input devices:");
         for device in host.input_devices().unwrap() {
             if let Ok(name) = device.name() {
                 println!("  - {}", name);
             }
         }
+        return Ok(());
+    }
 
-        let device = host.default_input_device().expect("No input device available");
-        println!("Using: {}", device.name().unwrap_or_default());
+    let device = select_device(&host, args.device.as_deref());
+    let sample_rate = negotiate_sample_rate(&device, SAMPLE_RATE);
+    println!("Using: {} @ {} Hz", device.name().unwrap_or_default(), sample_rate);
+
+    // Broadcast raw audio to every connected client; each client runs its
+    // own analyzer so different clients can request different settings.
+    let (tx, _) = broadcast::channel::<Vec<f32>>(16);
+    let tx_clone = tx.clone();
 
+    std::thread::spawn(move || {
         let config = cpal::StreamConfig {
             channels: 1,
-            sample_rate: cpal::SampleRate(SAMPLE_RATE),
+            sample_rate: cpal::SampleRate(sample_rate),
             buffer_size: cpal::BufferSize::Fixed(256),
         };
 
@@ -227,11 +181,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             .build_input_stream(
                 &config,
                 move |data: &[f32], _: &cpal::InputCallbackInfo| {
-                    if let Ok(mut proc) = processor_clone.lock() {
-                        if let Some(fft_data) = proc.process(data) {
-                            let _ = tx_clone.send(fft_data);
-                        }
-                    }
+                    let _ = tx_clone.send(data.to_vec());
                 },
                 |err| eprintln!("Audio error: {}", err),
                 None,
@@ -247,47 +197,69 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     });
 
-    // Start WebSocket server
+    // Start server
     let addr = format!("0.0.0.0:{}", args.port);
     let listener = TcpListener::bind(&addr).await?;
-    println!("FFT server started on ws://{}", addr);
+    println!("FFT server started on {}://{}", args.transport, addr);
 
     while let Ok((stream, addr)) = listener.accept().await {
         let mut rx = tx.subscribe();
+        let mode = args.mode.clone();
 
         tokio::spawn(async move {
-            let ws_stream = tokio_tungstenite::accept_async(stream)
-                .await
-                .expect("WebSocket handshake failed");
-
-            let (mut write, mut read) = ws_stream.split();
+            // Raw TCP clients are output-only (no `configure` channel back
+            // to the server), so they skip the WebSocket handshake and the
+            // incoming-message half of the select loop below.
+            let (mut writer, mut read) = match transport_kind {
+                TransportKind::WebSocket => {
+                    let ws_stream = tokio_tungstenite::accept_async(stream)
+                        .await
+                        .expect("WebSocket handshake failed");
+                    let (write, read) = ws_stream.split();
+                    (Writer::WebSocket(write), Some(read))
+                }
+                TransportKind::Tcp => (Writer::Tcp(stream), None),
+            };
+            let mut keystream = obfuscation_seed.map(Keystream::new);
             println!("Client connected: {}", addr);
 
-            // Send config
-            let config = Config {
-                msg_type: "config".to_string(),
-                mode: "fft".to_string(),
-                bins: FFT_BINS,
-                fps: FFT_FPS,
-            };
-            let config_json = serde_json::to_string(&config).unwrap();
-            let _ = write.send(Message::Text(config_json)).await;
+            // Each connection gets its own analyzer so clients can
+            // negotiate independent settings via `configure` requests.
+            let mut analyzer = analyzer::from_mode(&mode, window, fft_size);
+            analyzer.set_samplerate(sample_rate as f32);
+
+            let config_json = serde_json::to_string(&analyzer.config().with_obfuscation_seed(obfuscation_seed)).unwrap();
+            let _ = writer.send_text(config_json).await;
 
-            // Forward FFT data to client
+            // Forward analyzer frames to client, and (WebSocket only)
+            // handle reconfiguration requests coming back from it.
             loop {
                 tokio::select! {
                     result = rx.recv() => {
                         match result {
-                            Ok(data) => {
-                                if write.send(Message::Binary(data)).await.is_err() {
-                                    break;
+                            Ok(samples) => {
+                                if let Some(frame) = analyzer.process_data(&samples) {
+                                    if !writer.send(frame, &mut keystream).await {
+                                        break;
+                                    }
                                 }
                             }
                             Err(_) => break,
                         }
                     }
-                    msg = read.next() => {
+                    msg = async { read.as_mut().unwrap().next().await }, if read.is_some() => {
                         match msg {
+                            Some(Ok(Message::Text(text))) => {
+                                if let Ok(req) = serde_json::from_str::<ConfigureRequest>(&text) {
+                                    if req.msg_type.as_deref() == Some("configure") {
+                                        analyzer.configure(&req);
+                                        let config_json = serde_json::to_string(&analyzer.config().with_obfuscation_seed(obfuscation_seed)).unwrap();
+                                        if !writer.send_text(config_json).await {
+                                            break;
+                                        }
+                                    }
+                                }
+                            }
                             Some(Ok(_)) => continue,
                             _ => break,
                         }
@@ -0,0 +1,103 @@
+//! Output transport abstraction.
+//!
+//! A client can consume the stream either as a WebSocket (the original,
+//! browser-facing path) or as a raw TCP socket (for lightweight native
+//! consumers that don't want the WebSocket handshake/framing overhead).
+//! [`Writer`] hides that choice behind one `send`/`send_text` pair so the
+//! per-connection loop in `main` doesn't need to care which transport it's
+//! talking to.
+//!
+//! Raw TCP has no built-in message boundaries, so each payload is sent
+//! length-prefixed: a big-endian `u32` byte count followed by the payload.
+//! WebSocket already frames messages for us, so that variant just forwards
+//! to `tungstenite`.
+
+use futures_util::SinkExt;
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::WebSocketStream;
+
+/// Selects which transport newly accepted connections should use.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransportKind {
+    WebSocket,
+    Tcp,
+}
+
+impl std::str::FromStr for TransportKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "ws" | "websocket" => Ok(Self::WebSocket),
+            "tcp" => Ok(Self::Tcp),
+            other => Err(format!("unknown transport \"{other}\" (expected ws, tcp)")),
+        }
+    }
+}
+
+/// A per-connection output sink, generic over the negotiated transport.
+pub enum Writer {
+    WebSocket(futures_util::stream::SplitSink<WebSocketStream<TcpStream>, Message>),
+    Tcp(TcpStream),
+}
+
+impl Writer {
+    /// Send a binary analyzer frame, obfuscating it first if `keystream` is set.
+    pub async fn send(&mut self, mut data: Vec<u8>, keystream: &mut Option<Keystream>) -> bool {
+        if let Some(keystream) = keystream {
+            keystream.apply(&mut data);
+        }
+        match self {
+            Self::WebSocket(write) => write.send(Message::Binary(data)).await.is_ok(),
+            Self::Tcp(stream) => send_framed(stream, &data).await,
+        }
+    }
+
+    /// Send a text message (handshake / config updates). Never obfuscated,
+    /// so both transports can always negotiate in the clear.
+    pub async fn send_text(&mut self, text: String) -> bool {
+        match self {
+            Self::WebSocket(write) => write.send(Message::Text(text)).await.is_ok(),
+            Self::Tcp(stream) => send_framed(stream, text.as_bytes()).await,
+        }
+    }
+}
+
+async fn send_framed(stream: &mut TcpStream, data: &[u8]) -> bool {
+    let len = (data.len() as u32).to_be_bytes();
+    stream.write_all(&len).await.is_ok() && stream.write_all(data).await.is_ok()
+}
+
+/// A seeded XOR keystream used to lightly obfuscate frames on the wire.
+/// This is not encryption - it's meant to keep casual packet inspection
+/// from making sense of the stream, not to resist a motivated attacker.
+/// The seed is sent to the client in the `config` handshake so it can
+/// derive the same stream and undo it.
+pub struct Keystream {
+    state: u32,
+}
+
+impl Keystream {
+    pub fn new(seed: u32) -> Self {
+        // xorshift32 can't start from zero (it's a fixed point), so nudge
+        // a zero seed to a small nonzero constant.
+        Self { state: if seed == 0 { 0x9e3779b9 } else { seed } }
+    }
+
+    fn next_byte(&mut self) -> u8 {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.state = x;
+        (x & 0xff) as u8
+    }
+
+    fn apply(&mut self, data: &mut [u8]) {
+        for byte in data.iter_mut() {
+            *byte ^= self.next_byte();
+        }
+    }
+}
@@ -0,0 +1,65 @@
+//! Selectable apodization windows applied before an FFT.
+//!
+//! Different windows trade off main-lobe width against side-lobe
+//! suppression; which one to use depends on the signal being analyzed, so
+//! it's exposed as a `--window` flag / `configure` field rather than fixed.
+
+use std::f32::consts::PI;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WindowKind {
+    Hann,
+    Hamming,
+    Blackman,
+    BlackmanHarris,
+    Rectangular,
+}
+
+impl FromStr for WindowKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "hann" => Ok(Self::Hann),
+            "hamming" => Ok(Self::Hamming),
+            "blackman" => Ok(Self::Blackman),
+            "blackman-harris" | "blackmanharris" => Ok(Self::BlackmanHarris),
+            "rectangular" | "rect" | "none" => Ok(Self::Rectangular),
+            other => Err(format!(
+                "unknown window \"{other}\" (expected hann, hamming, blackman, blackman-harris, rectangular)"
+            )),
+        }
+    }
+}
+
+impl WindowKind {
+    /// Build the coefficient vector for a window of the given size,
+    /// along with its coherent gain (sum of coefficients / size) so
+    /// magnitude normalization stays correct across windows rather than
+    /// always dividing by the buffer size.
+    pub fn build(self, size: usize) -> (Vec<f32>, f32) {
+        let n = size as f32;
+        let coeffs: Vec<f32> = (0..size)
+            .map(|i| {
+                let x = i as f32;
+                match self {
+                    Self::Hann => 0.5 * (1.0 - (2.0 * PI * x / (n - 1.0)).cos()),
+                    Self::Hamming => 0.54 - 0.46 * (2.0 * PI * x / (n - 1.0)).cos(),
+                    Self::Blackman => {
+                        0.42 - 0.5 * (2.0 * PI * x / (n - 1.0)).cos() + 0.08 * (4.0 * PI * x / (n - 1.0)).cos()
+                    }
+                    Self::BlackmanHarris => {
+                        0.35875 - 0.48829 * (2.0 * PI * x / (n - 1.0)).cos()
+                            + 0.14128 * (4.0 * PI * x / (n - 1.0)).cos()
+                            - 0.01168 * (6.0 * PI * x / (n - 1.0)).cos()
+                    }
+                    Self::Rectangular => 1.0,
+                }
+            })
+            .collect();
+
+        let coherent_gain = coeffs.iter().sum::<f32>() / n;
+        (coeffs, coherent_gain)
+    }
+}